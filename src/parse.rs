@@ -1,4 +1,5 @@
 use std::io::Write;
+use std::path::PathBuf;
 
 use ansi_term::Colour::{Blue, Yellow};
 use console::strip_ansi_codes;
@@ -7,10 +8,12 @@ use crate::bat::assets::HighlightingAssets;
 use crate::cli;
 use crate::draw;
 use crate::paint::{Config, Painter, NO_BACKGROUND_COLOR_STYLE_MODIFIER};
+use crate::parse::parse_git_blame::{parse_blame_line, BlameLine};
 use crate::parse::parse_git_diff::{
     get_file_change_description_from_diff_line, get_file_extension_from_diff_line,
-    parse_hunk_metadata,
+    get_file_path_from_diff_line, get_n_parents_from_hunk_header, parse_hunk_metadata,
 };
+use crate::parse::parse_git_submodule::{get_subproject_commit_sha, parse_submodule_status_line};
 
 #[derive(Debug, PartialEq)]
 pub enum State {
@@ -20,6 +23,8 @@ pub enum State {
     HunkZero,   // In hunk; unchanged line
     HunkMinus,  // In hunk; removed line
     HunkPlus,   // In hunk; added line
+    Blame,      // In a line of `git blame` output
+    Submodule,  // In a submodule diff summary line
     Unknown,
 }
 
@@ -56,18 +61,50 @@ pub fn delta(
         plus_lines: Vec::new(),
         minus_line_style_sections: Vec::new(),
         plus_line_style_sections: Vec::new(),
+        // Line-number gutters, index-aligned with `minus_lines`/`plus_lines`. Kept separate so
+        // that `paint_buffered_lines()` composes them onto each line *after* syntax highlighting
+        // and word-diffing, rather than the gutter text itself being fed to the highlighter.
+        minus_gutters: Vec::new(),
+        plus_gutters: Vec::new(),
+        // Index-aligned with `minus_lines`/`plus_lines`. `minus_lines`/`plus_lines` always hold
+        // clean, ANSI-free text for the syntax highlighter and word-differ to operate on; when
+        // `Some`, the override here is what `paint_buffered_lines()` actually emits for that
+        // line instead of the highlighted text, letting git's own `--color-moved` SGR codes
+        // through without ever having been lexed as source text.
+        minus_raw_style_overrides: Vec::new(),
+        plus_raw_style_overrides: Vec::new(),
         output_buffer: String::new(),
         writer: writer,
         syntax: None,
         config: config,
+        minus_line_number: 0,
+        plus_line_number: 0,
     };
 
     let mut state = State::Unknown;
+    // Number of parents of the commit being diffed against, i.e. 1 for an ordinary diff and
+    // >1 for the combined diff format emitted for merge commits (`git show`, `git diff -c`).
+    let mut n_parents: usize = 1;
+    // Absolute path of the file under the current "diff --" section, used to build
+    // --hyperlinks targets for the file-meta and hunk-meta header lines.
+    let mut file_path: Option<PathBuf> = None;
+    // Human-readable description of the current "diff --" section (e.g. "added: path"), reused
+    // for the compact submodule summary line.
+    let mut file_description = String::new();
+    // The old-side commit sha of a "-Subproject commit <sha>" hunk line seen so far this hunk,
+    // awaiting a matching "+Subproject commit <sha>" line to render a compact summary.
+    let mut pending_submodule_sha: Option<String> = None;
 
     for raw_line in lines {
         let line = strip_ansi_codes(&raw_line).to_string();
         if line.starts_with("commit") {
             painter.paint_buffered_lines();
+            flush_pending_submodule(
+                &mut painter,
+                &file_description,
+                &mut pending_submodule_sha,
+                config,
+            )?;
             state = State::CommitMeta;
             if config.opt.commit_style != cli::SectionStyle::Plain {
                 painter.emit()?;
@@ -76,26 +113,89 @@ pub fn delta(
             }
         } else if line.starts_with("diff --") {
             painter.paint_buffered_lines();
+            flush_pending_submodule(
+                &mut painter,
+                &file_description,
+                &mut pending_submodule_sha,
+                config,
+            )?;
             state = State::FileMeta;
             painter.syntax = match get_file_extension_from_diff_line(&line) {
                 Some(extension) => assets.syntax_set.find_syntax_by_extension(extension),
                 None => None,
             };
+            file_path = get_file_path_from_diff_line(&line)
+                .and_then(|path| resolve_absolute_path(path, config));
+            file_description = get_file_change_description_from_diff_line(&line);
             if config.opt.file_style != cli::SectionStyle::Plain {
                 painter.emit()?;
-                write_file_meta_header_line(&mut painter, &raw_line, config)?;
+                write_file_meta_header_line(&mut painter, &raw_line, file_path.as_ref(), config)?;
                 continue;
             }
+        } else if line.starts_with("Submodule ") {
+            painter.paint_buffered_lines();
+            state = State::Submodule;
+            if config.opt.file_style != cli::SectionStyle::Plain {
+                if let Some((path, old_sha, new_sha)) = parse_submodule_status_line(&line) {
+                    painter.emit()?;
+                    write_submodule_summary_line(&mut painter, path, old_sha, new_sha, config)?;
+                    continue;
+                }
+            }
         } else if line.starts_with("@@") {
             state = State::HunkMeta;
+            n_parents = get_n_parents_from_hunk_header(&line);
+            let (code_fragment, minus_start, plus_start) = parse_hunk_metadata(&line, n_parents);
+            painter.minus_line_number = minus_start.parse().unwrap_or(0);
+            painter.plus_line_number = plus_start.parse().unwrap_or(0);
             if config.opt.hunk_style != cli::SectionStyle::Plain {
                 painter.emit()?;
-                write_hunk_meta_line(&mut painter, &line, config)?;
+                write_hunk_meta_line(
+                    &mut painter,
+                    &code_fragment,
+                    &plus_start,
+                    file_path.as_ref(),
+                    config,
+                )?;
                 continue;
             }
-        } else if state.is_in_hunk() && painter.syntax.is_some() {
-            state = paint_hunk_line(state, &mut painter, &line, config);
+        } else if state.is_in_hunk() {
+            let prefix_len = n_parents.min(line.len());
+            let (prefix, content) = line.split_at(prefix_len);
+            let subproject_sha = if config.opt.file_style != cli::SectionStyle::Plain {
+                get_subproject_commit_sha(content)
+            } else {
+                None
+            };
+            if let Some(sha) = subproject_sha {
+                if prefix.contains('-') {
+                    // Leave `state` alone (it's still a hunk state) so that the matching
+                    // "+Subproject commit <sha>" line, on the next iteration, still satisfies
+                    // `state.is_in_hunk()` and reaches this branch instead of falling through
+                    // to raw passthrough.
+                    pending_submodule_sha = Some(sha.to_string());
+                } else {
+                    state = State::Submodule;
+                    painter.emit()?;
+                    write_submodule_summary_line(
+                        &mut painter,
+                        &file_description,
+                        pending_submodule_sha.as_deref().unwrap_or("(new)"),
+                        sha,
+                        config,
+                    )?;
+                    pending_submodule_sha = None;
+                }
+                continue;
+            } else if painter.syntax.is_some() {
+                state = paint_hunk_line(state, &mut painter, &line, &raw_line, n_parents, config);
+                painter.emit()?;
+                continue;
+            }
+        } else if let Some(blame_line) = parse_blame_line(&line) {
+            state = State::Blame;
             painter.emit()?;
+            write_blame_line(&mut painter, &blame_line, assets, config)?;
             continue;
         }
         if state == State::FileMeta && config.opt.file_style != cli::SectionStyle::Plain {
@@ -107,11 +207,34 @@ pub fn delta(
         }
     }
 
+    flush_pending_submodule(
+        &mut painter,
+        &file_description,
+        &mut pending_submodule_sha,
+        config,
+    )?;
     painter.paint_buffered_lines();
     painter.emit()?;
     Ok(())
 }
 
+/// If a "-Subproject commit <sha>" line was seen without a later matching "+" line (e.g. the
+/// submodule itself was deleted), render the pending compact summary before moving on.
+fn flush_pending_submodule(
+    painter: &mut Painter,
+    file_description: &str,
+    pending_submodule_sha: &mut Option<String>,
+    config: &Config,
+) -> std::io::Result<()> {
+    if let Some(old_sha) = pending_submodule_sha.take() {
+        if config.opt.file_style != cli::SectionStyle::Plain {
+            painter.emit()?;
+            write_submodule_summary_line(painter, file_description, &old_sha, "(deleted)", config)?;
+        }
+    }
+    Ok(())
+}
+
 fn write_commit_meta_header_line(
     painter: &mut Painter,
     line: &str,
@@ -135,6 +258,39 @@ fn write_commit_meta_header_line(
 fn write_file_meta_header_line(
     painter: &mut Painter,
     line: &str,
+    file_path: Option<&PathBuf>,
+    config: &Config,
+) -> std::io::Result<()> {
+    let draw_fn = match config.opt.file_style {
+        cli::SectionStyle::Box => draw::write_boxed_with_line,
+        cli::SectionStyle::Underline => draw::write_underlined,
+        cli::SectionStyle::Plain => panic!(),
+    };
+    let ansi_style = Blue.bold();
+    let styled_description = ansi_style
+        .paint(get_file_change_description_from_diff_line(&line))
+        .to_string();
+    let text = match (config.opt.hyperlinks, file_path) {
+        (true, Some(path)) => hyperlink(&file_hyperlink_url(path, None), &styled_description),
+        _ => styled_description,
+    };
+    draw_fn(
+        painter.writer,
+        &text,
+        config.terminal_width,
+        ansi_style,
+        true,
+    )?;
+    Ok(())
+}
+
+/// Render a compact one-line summary for a submodule diff, styled like a file-meta header
+/// rather than as a full hunk: "<path>  old_sha ⟶ new_sha".
+fn write_submodule_summary_line(
+    painter: &mut Painter,
+    file_description: &str,
+    old_sha: &str,
+    new_sha: &str,
     config: &Config,
 ) -> std::io::Result<()> {
     let draw_fn = match config.opt.file_style {
@@ -143,9 +299,12 @@ fn write_file_meta_header_line(
         cli::SectionStyle::Plain => panic!(),
     };
     let ansi_style = Blue.bold();
+    let text = ansi_style
+        .paint(format!("{}  {} ⟶ {}", file_description, old_sha, new_sha))
+        .to_string();
     draw_fn(
         painter.writer,
-        &ansi_style.paint(get_file_change_description_from_diff_line(&line)),
+        &text,
         config.terminal_width,
         ansi_style,
         true,
@@ -153,20 +312,25 @@ fn write_file_meta_header_line(
     Ok(())
 }
 
-fn write_hunk_meta_line(painter: &mut Painter, line: &str, config: &Config) -> std::io::Result<()> {
+fn write_hunk_meta_line(
+    painter: &mut Painter,
+    code_fragment: &str,
+    line_number: &str,
+    file_path: Option<&PathBuf>,
+    config: &Config,
+) -> std::io::Result<()> {
     let draw_fn = match config.opt.hunk_style {
         cli::SectionStyle::Box => draw::write_boxed,
         cli::SectionStyle::Underline => draw::write_underlined,
         cli::SectionStyle::Plain => panic!(),
     };
     let ansi_style = Blue.normal();
-    let (code_fragment, line_number) = parse_hunk_metadata(&line);
     if code_fragment.len() > 0 {
         painter.paint_lines(
-            vec![code_fragment.clone()],
+            vec![code_fragment.to_string()],
             vec![vec![(
                 NO_BACKGROUND_COLOR_STYLE_MODIFIER,
-                code_fragment.clone(),
+                code_fragment.to_string(),
             )]],
         );
         painter.output_buffer.pop(); // trim newline
@@ -179,41 +343,221 @@ fn write_hunk_meta_line(painter: &mut Painter, line: &str, config: &Config) -> s
         )?;
         painter.output_buffer.truncate(0);
     }
-    writeln!(painter.writer, "\n{}", ansi_style.paint(line_number))?;
+    let styled_line_number = ansi_style.paint(&line_number).to_string();
+    let text = match (config.opt.hyperlinks, file_path) {
+        (true, Some(path)) => hyperlink(
+            &file_hyperlink_url(path, Some(&line_number)),
+            &styled_line_number,
+        ),
+        _ => styled_line_number,
+    };
+    writeln!(painter.writer, "\n{}", text)?;
+    Ok(())
+}
+
+/// A small fixed palette of background colors cycled across distinct commits in `git blame`
+/// output, so that every line belonging to the same commit gets the same highlight.
+const BLAME_PALETTE: [u8; 6] = [24, 60, 96, 132, 168, 204];
+
+/// Hash `commit` into a stable index into `BLAME_PALETTE`, so the same commit always maps to
+/// the same color across an invocation (FNV-1a).
+fn blame_palette_index(commit: &str) -> usize {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in commit.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    (hash as usize) % BLAME_PALETTE.len()
+}
+
+fn blame_background_color(commit: &str) -> ansi_term::Colour {
+    ansi_term::Colour::Fixed(BLAME_PALETTE[blame_palette_index(commit)])
+}
+
+/// Render the "(author date)" column of a blame line using `config.opt.blame_format`, a
+/// configurable format string in which `{}` is replaced by the raw text captured between the
+/// parentheses (e.g. "Jane Doe 2020-01-02 12:00:00 +0000  42").
+fn format_blame_metadata(raw: &str, config: &Config) -> String {
+    config.opt.blame_format.replace("{}", raw)
+}
+
+fn write_blame_line(
+    painter: &mut Painter,
+    blame_line: &BlameLine,
+    assets: &HighlightingAssets,
+    config: &Config,
+) -> std::io::Result<()> {
+    let background = blame_background_color(blame_line.commit);
+    let metadata = blame_line
+        .author_date
+        .map(|raw| format_blame_metadata(raw, config))
+        .unwrap_or_default();
+    // Prefer the extension of the file actually being blamed (available when `git blame` was
+    // run with `-f`/`--show-name`); fall back to the manually configured extension, since plain
+    // `git blame <file>` output carries no filename at all.
+    let extension = blame_line
+        .file
+        .and_then(|file| std::path::Path::new(file).extension())
+        .and_then(|ext| ext.to_str())
+        .map(String::from)
+        .or_else(|| config.opt.blame_file_extension.clone());
+    painter.syntax = extension
+        .as_ref()
+        .and_then(|extension| assets.syntax_set.find_syntax_by_extension(extension));
+    painter.paint_lines(
+        vec![blame_line.content.to_string()],
+        vec![vec![(
+            NO_BACKGROUND_COLOR_STYLE_MODIFIER,
+            blame_line.content.to_string(),
+        )]],
+    );
+    painter.output_buffer.pop(); // trim newline
+    let style = ansi_term::Style::new().on(background);
+    writeln!(
+        painter.writer,
+        "{} {}",
+        style.paint(format!("{} {}", blame_line.commit, metadata)),
+        painter.output_buffer
+    )?;
+    painter.output_buffer.truncate(0);
     Ok(())
 }
 
-fn paint_hunk_line(state: State, painter: &mut Painter, line: &str, config: &Config) -> State {
-    match line.chars().next() {
+/// Resolve a diff-relative path (always relative to the repo's top-level directory, per git's
+/// own diff output) to an absolute path. Falls back to the current working directory if the
+/// repo root couldn't be determined, e.g. when delta is used outside of a git repo.
+fn resolve_absolute_path(relative_path: &str, config: &Config) -> Option<PathBuf> {
+    let root = config
+        .repo_root
+        .clone()
+        .or_else(|| std::env::current_dir().ok())?;
+    Some(root.join(relative_path))
+}
+
+/// Build a `file://` URL for `path`, using the `file-line-column:` scheme understood by some
+/// editor integrations when a line number is available.
+fn file_hyperlink_url(path: &PathBuf, line_number: Option<&str>) -> String {
+    match line_number {
+        Some(n) if !n.is_empty() => format!("file-line-column://{}:{}", path.display(), n),
+        _ => format!("file://{}", path.display()),
+    }
+}
+
+/// Wrap `text` in an OSC 8 terminal hyperlink escape sequence pointing at `url`. This is
+/// generated on output (never on the stripped input lines) so that `strip_ansi_codes`, which
+/// is applied to every incoming line, cannot strip it.
+fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// Classify a hunk line's leading `n_parents` columns as minus, plus, or zero (context).
+/// In the combined diff format each parent contributes one leading column of `-`/`+`/` `;
+/// a line is minus if any column is `-`, else plus if any column is `+`, else context.
+fn classify_hunk_line(columns: &str) -> Option<char> {
+    if columns.contains('-') {
+        Some('-')
+    } else if columns.contains('+') {
+        Some('+')
+    } else {
+        None
+    }
+}
+
+fn paint_hunk_line(
+    state: State,
+    painter: &mut Painter,
+    line: &str,
+    raw_line: &str,
+    n_parents: usize,
+    config: &Config,
+) -> State {
+    let columns = if line.len() >= n_parents {
+        &line[..n_parents]
+    } else {
+        line
+    };
+    match classify_hunk_line(columns) {
         Some('-') => {
             if state == State::HunkPlus {
                 painter.paint_buffered_lines();
             }
-            painter.minus_lines.push(prepare(&line, config));
+            let raw_style_override = if config.opt.inspect_raw_lines
+                && has_git_move_style(raw_line, DEFAULT_MINUS_SGR)
+            {
+                Some(prepare_preserving_raw_style(raw_line, n_parents, config))
+            } else {
+                None
+            };
+            painter.minus_lines.push(prepare(&line, n_parents, config));
+            painter.minus_raw_style_overrides.push(raw_style_override);
+            if config.opt.line_numbers {
+                painter.minus_gutters.push(format_line_number_gutter(
+                    Some(painter.minus_line_number),
+                    None,
+                    config,
+                ));
+            }
+            painter.minus_line_number += 1;
             State::HunkMinus
         }
         Some('+') => {
-            painter.plus_lines.push(prepare(&line, config));
+            let raw_style_override =
+                if config.opt.inspect_raw_lines && has_git_move_style(raw_line, DEFAULT_PLUS_SGR) {
+                    Some(prepare_preserving_raw_style(raw_line, n_parents, config))
+                } else {
+                    None
+                };
+            painter.plus_lines.push(prepare(&line, n_parents, config));
+            painter.plus_raw_style_overrides.push(raw_style_override);
+            if config.opt.line_numbers {
+                painter.plus_gutters.push(format_line_number_gutter(
+                    None,
+                    Some(painter.plus_line_number),
+                    config,
+                ));
+            }
+            painter.plus_line_number += 1;
             State::HunkPlus
         }
         _ => {
             painter.paint_buffered_lines();
-            let line = prepare(&line, config);
+            let line = prepare(&line, n_parents, config);
+            // `output_buffer` may already hold the minus/plus lines just flushed above, so
+            // remember where this line's own text starts.
+            let line_start = painter.output_buffer.len();
             painter.paint_lines(
                 vec![line.clone()],
                 vec![vec![(NO_BACKGROUND_COLOR_STYLE_MODIFIER, line.clone())]],
             );
+            if config.opt.line_numbers {
+                // Prepend the gutter to this line's already-highlighted text in `output_buffer`
+                // rather than writing it out here directly: the caller's `painter.emit()?`, right
+                // after this function returns, is what actually writes (and propagates I/O
+                // errors for) `output_buffer`'s contents.
+                let gutter = format_line_number_gutter(
+                    Some(painter.minus_line_number),
+                    Some(painter.plus_line_number),
+                    config,
+                );
+                painter.output_buffer.insert_str(line_start, &gutter);
+            }
+            painter.minus_line_number += 1;
+            painter.plus_line_number += 1;
             State::HunkZero
         }
     }
 }
 
-/// Replace initial -/+ character with ' ' and pad to width.
-fn prepare(_line: &str, config: &Config) -> String {
+/// Replace the leading `n_parents` -/+ columns with spaces and pad to width. The line-number
+/// gutter is *not* handled here: it is composed onto the line by the caller after syntax
+/// highlighting and word-diffing have run, so gutter text (digits, "│") is never fed to the
+/// highlighter or included in word-diff alignment between the minus/plus buffers.
+fn prepare(_line: &str, n_parents: usize, config: &Config) -> String {
     let mut line = String::new();
     if _line.len() > 0 {
-        line.push_str(" ");
-        line.push_str(&_line[1..]);
+        let n = n_parents.min(_line.len());
+        line.push_str(" ".repeat(n).as_str());
+        line.push_str(&_line[n..]);
     }
     match config.width {
         Some(width) => {
@@ -226,6 +570,134 @@ fn prepare(_line: &str, config: &Config) -> String {
     line
 }
 
+/// Format the "old │ new" line-number gutter prepended to each hunk line, leaving the
+/// appropriate side blank for added/removed lines.
+fn format_line_number_gutter(
+    minus_number: Option<usize>,
+    plus_number: Option<usize>,
+    config: &Config,
+) -> String {
+    let width = config.opt.line_numbers_width;
+    let minus = minus_number.map_or(String::new(), |n| n.to_string());
+    let plus = plus_number.map_or(String::new(), |n| n.to_string());
+    format!("{:>width$} │ {:<width$} ", minus, plus, width = width)
+}
+
+/// The SGR codes git itself emits for an ordinary (not moved) minus/plus line, before any
+/// `diff.colorMoved` styling is applied.
+const DEFAULT_MINUS_SGR: &str = "\x1b[31m";
+const DEFAULT_PLUS_SGR: &str = "\x1b[32m";
+
+/// The first ANSI SGR escape sequence ("\x1b[...m") present in `raw_line`, if any.
+fn first_sgr_code(raw_line: &str) -> Option<&str> {
+    let start = raw_line.find("\x1b[")?;
+    let rest = &raw_line[start..];
+    let end = rest.find('m')?;
+    Some(&rest[..=end])
+}
+
+/// True if `raw_line` already carries an ANSI style other than git's plain default minus/plus
+/// color, indicating git applied `diff.colorMoved`/`--color-moved` styling to this line.
+fn has_git_move_style(raw_line: &str, default_sgr: &str) -> bool {
+    match first_sgr_code(raw_line) {
+        Some(code) => code != default_sgr,
+        None => false,
+    }
+}
+
+/// Like `prepare`, but strips the leading `n_parents` -/+ columns from `raw_line` while
+/// preserving any embedded ANSI escape sequences, instead of stripping them from the
+/// already-`strip_ansi_codes`-d `line`. The result is stored as a raw-style override rather
+/// than pushed into `minus_lines`/`plus_lines` directly: those buffers must stay ANSI-free
+/// since they are fed to the syntax highlighter and word-differ, so git's own
+/// `--color-moved` styling is spliced back in by `paint_buffered_lines()` only once it has
+/// finished highlighting/diffing the clean text. As with `prepare`, the line-number gutter
+/// is composed on afterwards by the caller, not here.
+fn prepare_preserving_raw_style(raw_line: &str, n_parents: usize, config: &Config) -> String {
+    let mut line = strip_leading_hunk_columns_preserving_ansi(raw_line, n_parents);
+    match config.width {
+        Some(width) => {
+            let visible_len = strip_ansi_codes(&line).chars().count();
+            if visible_len < width {
+                line.push_str(&" ".repeat(width - visible_len));
+            }
+        }
+        _ => (),
+    }
+    line
+}
+
+/// Replace the leading `n_parents` -/+/space columns of `raw_line` with spaces, passing any
+/// ANSI escape sequences through untouched.
+fn strip_leading_hunk_columns_preserving_ansi(raw_line: &str, n_parents: usize) -> String {
+    let mut result = String::new();
+    let mut chars = raw_line.chars();
+    let mut stripped = 0;
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            result.push(c);
+            for next in &mut chars {
+                result.push(next);
+                if next == 'm' {
+                    break;
+                }
+            }
+            continue;
+        }
+        if stripped < n_parents {
+            stripped += 1;
+            result.push(' ');
+            continue;
+        }
+        result.push(c);
+    }
+    result
+}
+
+#[cfg(test)]
+mod color_moved_tests {
+    use super::*;
+
+    #[test]
+    fn test_first_sgr_code() {
+        assert_eq!(first_sgr_code("\x1b[31mfoo"), Some("\x1b[31m"));
+        assert_eq!(first_sgr_code("\x1b[38;5;208mfoo"), Some("\x1b[38;5;208m"));
+        assert_eq!(first_sgr_code("plain text, no escapes"), None);
+    }
+
+    #[test]
+    fn test_first_sgr_code_returns_the_first_of_several() {
+        assert_eq!(first_sgr_code("\x1b[31mfoo\x1b[0m"), Some("\x1b[31m"));
+    }
+
+    #[test]
+    fn test_has_git_move_style() {
+        assert!(!has_git_move_style("\x1b[31m-foo", DEFAULT_MINUS_SGR));
+        assert!(has_git_move_style("\x1b[33m-foo", DEFAULT_MINUS_SGR));
+        assert!(!has_git_move_style("-foo", DEFAULT_MINUS_SGR));
+    }
+
+    #[test]
+    fn test_strip_leading_hunk_columns_preserving_ansi() {
+        assert_eq!(
+            strip_leading_hunk_columns_preserving_ansi("-foo", 1),
+            " foo"
+        );
+        assert_eq!(
+            strip_leading_hunk_columns_preserving_ansi("--foo", 2),
+            "  foo"
+        );
+    }
+
+    #[test]
+    fn test_strip_leading_hunk_columns_preserving_ansi_keeps_escapes() {
+        assert_eq!(
+            strip_leading_hunk_columns_preserving_ansi("\x1b[33m-foo\x1b[0m", 1),
+            "\x1b[33m foo\x1b[0m"
+        );
+    }
+}
+
 mod parse_git_diff {
     use std::path::Path;
 
@@ -248,6 +720,29 @@ mod parse_git_diff {
         }
     }
 
+    /// Given a hunk header, return the number of parents of the commit being diffed:
+    /// 1 for an ordinary diff ("@@ ... @@"), N for a combined diff ("@@@ ... @@@", N = 2),
+    /// produced by `git show`/`git diff -c` on a merge commit with N parents.
+    pub fn get_n_parents_from_hunk_header(line: &str) -> usize {
+        let n_at_signs = line.chars().take_while(|c| *c == '@').count();
+        if n_at_signs > 1 {
+            n_at_signs - 1
+        } else {
+            1
+        }
+    }
+
+    /// Given input like "diff --git a/src/main.rs b/src/main.rs", return the file's current
+    /// path: the new path, or the old path if the file was deleted.
+    pub fn get_file_path_from_diff_line(line: &str) -> Option<&str> {
+        match get_file_paths_from_diff_line(line) {
+            (Some(file_1), Some("/dev/null")) => Some(file_1),
+            (_, Some(file_2)) => Some(file_2),
+            (Some(file_1), None) => Some(file_1),
+            (None, None) => None,
+        }
+    }
+
     // TODO: Don't parse the line twice (once for change description and once for extensions).
     pub fn get_file_change_description_from_diff_line(line: &str) -> String {
         match get_file_paths_from_diff_line(line) {
@@ -261,31 +756,47 @@ mod parse_git_diff {
 
     /// Given input like
     /// "@@ -74,15 +74,14 @@ pub fn delta("
-    /// Return " pub fn delta("
-    pub fn parse_hunk_metadata(line: &str) -> (String, String) {
-        let mut iter = line.split("@@").skip(1);
-        let line_number = iter
-            .next()
-            .and_then(|s| {
-                s.split("+")
-                    .skip(1)
-                    .next()
-                    .and_then(|s| s.split(",").next())
-            })
+    /// Return (" pub fn delta(", "74", "74"), i.e. (code fragment, minus start, plus start).
+    /// For a combined diff with n_parents > 1, input like
+    /// "@@@ -74,15 -80,15 +74,14 @@@ pub fn delta(" is also accepted; the start of the first
+    /// parent's `-` range is returned.
+    pub fn parse_hunk_metadata(line: &str, n_parents: usize) -> (String, String, String) {
+        let at_signs = "@".repeat(n_parents + 1);
+        let mut iter = line.split(&at_signs as &str).skip(1);
+        let ranges = iter.next().unwrap_or("");
+        let minus_start = ranges
+            .split_whitespace()
+            .find(|s| s.starts_with('-'))
+            .and_then(|s| s[1..].split(',').next())
+            .unwrap_or("")
+            .to_string();
+        let plus_start = ranges
+            .split_whitespace()
+            .find(|s| s.starts_with('+'))
+            .and_then(|s| s[1..].split(',').next())
             .unwrap_or("")
             .to_string();
         let code_fragment = iter.next().unwrap_or("").to_string();
-        (code_fragment, line_number)
+        (code_fragment, minus_start, plus_start)
     }
 
+    /// Given input like "diff --git a/src/main.rs b/src/main.rs" return the two paths.
+    /// Also handles the two-token form used for combined diffs, e.g.
+    /// "diff --cc src/main.rs" or "diff --combined src/main.rs", which has no `a/`/`b/` pair.
     fn get_file_paths_from_diff_line(line: &str) -> (Option<&str>, Option<&str>) {
         let mut iter = line.split(" ");
         iter.next(); // diff
-        iter.next(); // --git
-        (
-            iter.next().and_then(|s| Some(&s[2..])),
-            iter.next().and_then(|s| Some(&s[2..])),
-        )
+        let mode = iter.next(); // --git, --cc, --combined
+        match mode {
+            Some("--cc") | Some("--combined") => {
+                let path = iter.next();
+                (path, path)
+            }
+            _ => (
+                iter.next().and_then(|s| Some(&s[2..])),
+                iter.next().and_then(|s| Some(&s[2..])),
+            ),
+        }
     }
 
     /// Given input like "diff --git a/src/main.rs b/src/main.rs"
@@ -293,11 +804,17 @@ mod parse_git_diff {
     fn get_file_extensions_from_diff_line(line: &str) -> (Option<&str>, Option<&str>) {
         let mut iter = line.split(" ");
         iter.next(); // diff
-        iter.next(); // --git
-        (
-            iter.next().and_then(|s| get_extension(&s[2..])),
-            iter.next().and_then(|s| get_extension(&s[2..])),
-        )
+        let mode = iter.next(); // --git, --cc, --combined
+        match mode {
+            Some("--cc") | Some("--combined") => {
+                let ext = iter.next().and_then(|s| get_extension(s));
+                (ext, ext)
+            }
+            _ => (
+                iter.next().and_then(|s| get_extension(&s[2..])),
+                iter.next().and_then(|s| get_extension(&s[2..])),
+            ),
+        }
     }
 
     /// Attempt to parse input as a file path and return extension as a &str.
@@ -334,10 +851,244 @@ mod parse_git_diff {
         #[test]
         fn test_parse_hunk_metadata() {
             assert_eq!(
-                parse_hunk_metadata("@@ -74,15 +75,14 @@ pub fn delta(\n"),
-                (" pub fn delta(\n".to_string(), "75".to_string())
+                parse_hunk_metadata("@@ -74,15 +75,14 @@ pub fn delta(\n", 1),
+                (
+                    " pub fn delta(\n".to_string(),
+                    "74".to_string(),
+                    "75".to_string()
+                )
+            );
+        }
+
+        #[test]
+        fn test_get_n_parents_from_hunk_header() {
+            assert_eq!(
+                get_n_parents_from_hunk_header("@@ -74,15 +75,14 @@ pub fn delta(\n"),
+                1
+            );
+            assert_eq!(
+                get_n_parents_from_hunk_header("@@@ -1,5 -1,5 +1,6 @@@\n"),
+                2
+            );
+        }
+
+        #[test]
+        fn test_parse_hunk_metadata_combined_diff() {
+            assert_eq!(
+                parse_hunk_metadata("@@@ -1,5 -1,5 +1,6 @@@ pub fn delta(\n", 2),
+                (
+                    " pub fn delta(\n".to_string(),
+                    "1".to_string(),
+                    "1".to_string()
+                )
+            );
+        }
+
+        #[test]
+        fn test_get_file_path_from_diff_line() {
+            assert_eq!(
+                get_file_path_from_diff_line("diff --git a/src/main.rs b/src/main.rs"),
+                Some("src/main.rs")
+            );
+        }
+
+        #[test]
+        fn test_get_file_extension_from_diff_line_combined() {
+            assert_eq!(
+                get_file_extension_from_diff_line("diff --cc src/main.rs"),
+                Some("rs")
+            );
+            assert_eq!(
+                get_file_extension_from_diff_line("diff --combined src/main.rs"),
+                Some("rs")
+            );
+        }
+    }
+}
+
+mod parse_git_blame {
+    /// The parts of one line of `git blame` output: the commit that introduced the line, the
+    /// blamed file's path (only present when `git blame -f`/`--show-name` is used, e.g. when
+    /// blaming multiple files or following renames), the optional "(author date tz lineno)"
+    /// column, and the blamed source content.
+    pub struct BlameLine<'a> {
+        pub commit: &'a str,
+        pub file: Option<&'a str>,
+        pub author_date: Option<&'a str>,
+        pub content: &'a str,
+    }
+
+    /// Detect and parse a line of `git blame` output, roughly matching
+    /// `^\^?[0-9a-f]{7,40}\s+(filename\s+)?(\(author date tz lineno\))?\s?content`. Returns
+    /// `None` if `line` doesn't start with something that looks like a blame commit hash
+    /// (boundary commits are prefixed with `^`).
+    pub fn parse_blame_line(line: &str) -> Option<BlameLine> {
+        let line = if line.starts_with('^') {
+            &line[1..]
+        } else {
+            line
+        };
+        let commit_end = line.find(char::is_whitespace)?;
+        let commit = &line[..commit_end];
+        if commit.len() < 7 || commit.len() > 40 || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let mut rest = line[commit_end..].trim_start();
+        // `git blame -f`/`--show-name` inserts the blamed file's path as its own column here,
+        // before the "(author date)" column. Only treat the leading token as a filename if it
+        // is itself followed by a "(...)" metadata column; otherwise it's just blamed content
+        // with no metadata at all, and `file` stays `None`.
+        let file = if !rest.starts_with('(') {
+            rest.find(char::is_whitespace).and_then(|file_end| {
+                let candidate = &rest[..file_end];
+                let remainder = rest[file_end..].trim_start();
+                if remainder.starts_with('(') {
+                    rest = remainder;
+                    Some(candidate)
+                } else {
+                    None
+                }
+            })
+        } else {
+            None
+        };
+        if rest.starts_with('(') {
+            match rest.find(')') {
+                Some(close) => Some(BlameLine {
+                    commit,
+                    file,
+                    author_date: Some(&rest[1..close]),
+                    content: rest[close + 1..].trim_start(),
+                }),
+                None => Some(BlameLine {
+                    commit,
+                    file,
+                    author_date: None,
+                    content: rest,
+                }),
+            }
+        } else {
+            Some(BlameLine {
+                commit,
+                file,
+                author_date: None,
+                content: rest,
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_blame_line() {
+            let blame_line =
+                parse_blame_line("b8d0f3b7 (Jane Doe 2020-01-02 12:00:00 +0000  42) fn delta(")
+                    .unwrap();
+            assert_eq!(blame_line.commit, "b8d0f3b7");
+            assert_eq!(blame_line.file, None);
+            assert_eq!(
+                blame_line.author_date,
+                Some("Jane Doe 2020-01-02 12:00:00 +0000  42")
+            );
+            assert_eq!(blame_line.content, "fn delta(");
+        }
+
+        #[test]
+        fn test_parse_blame_line_with_show_name() {
+            let blame_line = parse_blame_line(
+                "b8d0f3b7 src/parse.rs (Jane Doe 2020-01-02 12:00:00 +0000  42) fn delta(",
+            )
+            .unwrap();
+            assert_eq!(blame_line.commit, "b8d0f3b7");
+            assert_eq!(blame_line.file, Some("src/parse.rs"));
+            assert_eq!(
+                blame_line.author_date,
+                Some("Jane Doe 2020-01-02 12:00:00 +0000  42")
             );
+            assert_eq!(blame_line.content, "fn delta(");
+        }
+
+        #[test]
+        fn test_parse_blame_line_boundary_commit() {
+            let blame_line = parse_blame_line("^b8d0f3b fn delta(").unwrap();
+            assert_eq!(blame_line.commit, "b8d0f3b");
+            assert_eq!(blame_line.author_date, None);
+            assert_eq!(blame_line.content, "fn delta(");
+        }
+
+        #[test]
+        fn test_parse_blame_line_rejects_non_blame_lines() {
+            assert!(parse_blame_line("diff --git a/src/main.rs b/src/main.rs").is_none());
+            assert!(parse_blame_line("not a hash at all").is_none());
+        }
+    }
+}
+
+mod parse_git_submodule {
+    /// Given a hunk content line (with the leading -/+/space column(s) already stripped), e.g.
+    /// "Subproject commit abc1234", return the commit sha.
+    pub fn get_subproject_commit_sha(content: &str) -> Option<&str> {
+        if content.starts_with("Subproject commit ") {
+            Some(&content["Subproject commit ".len()..])
+        } else {
+            None
         }
     }
 
-}
\ No newline at end of file
+    /// Given input like "Submodule path/to/sub 0abc123..1def456 (commits)", as emitted by
+    /// `git diff --submodule=log` (or `git status`), return (path, old_sha, new_sha).
+    pub fn parse_submodule_status_line(line: &str) -> Option<(&str, &str, &str)> {
+        if !line.starts_with("Submodule ") {
+            return None;
+        }
+        let rest = &line["Submodule ".len()..];
+        let mut iter = rest.split_whitespace();
+        let path = iter.next()?;
+        let range = iter.next()?;
+        // The separator is ".." when one commit is an ancestor of the other, and "..." when
+        // neither is (e.g. after a rebase/force-push inside the submodule) — either way it's a
+        // run of dots, so split on that rather than assuming a fixed-width separator.
+        let mut shas = range.split(|c| c == '.').filter(|s| !s.is_empty());
+        let old_sha = shas.next()?;
+        let new_sha = shas.next().unwrap_or("");
+        Some((path, old_sha, new_sha))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_get_subproject_commit_sha() {
+            assert_eq!(
+                get_subproject_commit_sha("Subproject commit abc1234"),
+                Some("abc1234")
+            );
+            assert_eq!(get_subproject_commit_sha("not a subproject line"), None);
+        }
+
+        #[test]
+        fn test_parse_submodule_status_line() {
+            assert_eq!(
+                parse_submodule_status_line("Submodule path/to/sub 0abc123..1def456 (commits)"),
+                Some(("path/to/sub", "0abc123", "1def456"))
+            );
+            assert_eq!(
+                parse_submodule_status_line("diff --git a/src/main.rs b/src/main.rs"),
+                None
+            );
+        }
+
+        #[test]
+        fn test_parse_submodule_status_line_non_fast_forward() {
+            // Git uses "..." instead of ".." when neither commit is an ancestor of the other,
+            // e.g. after a rebase/force-push inside the submodule.
+            assert_eq!(
+                parse_submodule_status_line("Submodule path/to/sub 0abc123...1def456 (rewind)"),
+                Some(("path/to/sub", "0abc123", "1def456"))
+            );
+        }
+    }
+}